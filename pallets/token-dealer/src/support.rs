@@ -27,13 +27,134 @@ use sp_std::{
 	result,
 };
 use token_factory::{CurrencyId, Ticker};
-use xcm::v0::{Error, Junction, MultiAsset, MultiLocation, Result as XcmResult};
+use xcm::v0::{AssetInstance, Error, Junction, MultiAsset, MultiLocation, Result as XcmResult};
 use xcm_executor::traits::{LocationConversion, MatchesFungible, TransactAsset};
 
 pub trait CurrencyIdConversion<CurrencyId> {
 	fn from_asset(asset: &MultiAsset) -> Option<CurrencyId>;
 }
 
+/// Matcher associated type for `MultiCurrencyAdapter` to recognise non-fungible (NFT)
+/// assets and decode their `AssetInstance` into a local instance id, mirroring
+/// [`MatchesFungible`] for the fungible case.
+pub trait MatchesNonFungible<InstanceId> {
+	fn matches_nonfungible(a: &MultiAsset) -> Option<InstanceId>;
+}
+
+/// Hook invoked once `MultiCurrencyAdapter::deposit_asset` has successfully minted or
+/// transferred a *fungible* asset to `who`, before the adapter returns `Ok`.
+///
+/// This gives downstream pallets an extension point to react to incoming XCM transfers
+/// (route the funds on, emit a typed event, ...) without forking the adapter. The default
+/// `()` implementation is a no-op so existing configs compile unchanged.
+///
+/// Scoped to the fungible path only: NFT deposits have no `Balance` amount to report, and
+/// parked `UnknownAssets` deposits have no resolved `CurrencyId` to report it against, so
+/// neither path fires this hook.
+pub trait OnDepositSuccess<CurrencyId, AccountId, Balance> {
+	fn on_deposit(currency: &CurrencyId, who: &AccountId, amount: Balance) -> XcmResult;
+}
+
+impl<CurrencyId, AccountId, Balance> OnDepositSuccess<CurrencyId, AccountId, Balance> for () {
+	fn on_deposit(_currency: &CurrencyId, _who: &AccountId, _amount: Balance) -> XcmResult {
+		Ok(())
+	}
+}
+
+/// Hook invoked once `MultiCurrencyAdapter::withdraw_asset` has successfully burned or
+/// withdrawn a *fungible* asset from `who`, before the adapter returns `Ok`.
+///
+/// See [`OnDepositSuccess`] for the rationale and why the NFT and `UnknownAssets` paths don't
+/// fire this hook; the default `()` implementation is a no-op.
+pub trait OnWithdrawSuccess<CurrencyId, AccountId, Balance> {
+	fn on_withdraw(currency: &CurrencyId, who: &AccountId, amount: Balance) -> XcmResult;
+}
+
+impl<CurrencyId, AccountId, Balance> OnWithdrawSuccess<CurrencyId, AccountId, Balance> for () {
+	fn on_withdraw(_currency: &CurrencyId, _who: &AccountId, _amount: Balance) -> XcmResult {
+		Ok(())
+	}
+}
+
+/// Resolves a substitute account to credit when the intended recipient's deposit fails
+/// (frozen account, below existential deposit, mint cap hit, ...).
+///
+/// `error` is the original failure returned by the token factory, so implementations can
+/// branch on the reason (e.g. retry a frozen account but not a mint-cap rejection). Returning
+/// `Some(account)` makes the adapter retry the credit against `account` (e.g. a treasury/
+/// holding account) instead of dropping the incoming assets; returning `None` propagates the
+/// original failure. Implementations must be idempotent: the adapter only ever retries once,
+/// against the account returned here, so they should not re-enter the failed account.
+pub trait DepositFailureHandler<CurrencyId, AccountId, Balance, Error> {
+	fn on_deposit_failure(
+		currency: &CurrencyId,
+		who: &AccountId,
+		amount: Balance,
+		error: Error,
+	) -> Option<AccountId>;
+}
+
+impl<CurrencyId, AccountId, Balance, Error> DepositFailureHandler<CurrencyId, AccountId, Balance, Error> for () {
+	fn on_deposit_failure(
+		_currency: &CurrencyId,
+		_who: &AccountId,
+		_amount: Balance,
+		_error: Error,
+	) -> Option<AccountId> {
+		None
+	}
+}
+
+/// Registry for value attached to a `MultiLocation` whose asset doesn't (yet) map to a known
+/// `CurrencyId`.
+///
+/// `CurrencyIdConversion::from_asset` returns `None` for any asset location that hasn't been
+/// registered yet (e.g. a `GeneralKey` without a matching `CurrencyId`). Rather than drop such
+/// a transfer, the adapter parks the balance here, keyed by the asset's `location` and `who` —
+/// never by the whole `MultiAsset`, since that embeds `amount` and would leave a withdrawal of a
+/// different amount unable to find the parked balance — so the value isn't lost; a later
+/// governance/registration action can reconcile it into a real `CurrencyId` once the asset is
+/// registered.
+pub trait UnknownAssets<AccountId> {
+	/// Record `amount` of the unrecognised asset at `location` as belonging to `who`.
+	///
+	/// Implementations must fail closed: a backend that cannot actually persist the balance
+	/// (e.g. the no-op `()` impl) must return `Err`, since reporting `Ok` here tells the XCM
+	/// executor the deposit succeeded while the value is silently discarded.
+	fn deposit(location: &MultiLocation, who: &AccountId, amount: u128) -> XcmResult;
+
+	/// Decrement a previously recorded balance, failing if less than `amount` was ever
+	/// deposited for `(location, who)`.
+	fn withdraw(location: &MultiLocation, who: &AccountId, amount: u128) -> XcmResult;
+}
+
+impl<AccountId> UnknownAssets<AccountId> for () {
+	fn deposit(_location: &MultiLocation, _who: &AccountId, _amount: u128) -> XcmResult {
+		Err(())
+	}
+	fn withdraw(_location: &MultiLocation, _who: &AccountId, _amount: u128) -> XcmResult {
+		Err(())
+	}
+}
+
+/// A [`DepositFailureHandler`] that always retries against a single, statically configured
+/// holding account.
+pub struct FixedDepositFallback<AccountId, FallbackAccount>(PhantomData<(AccountId, FallbackAccount)>);
+impl<CurrencyId, AccountId, Balance, Error, FallbackAccount>
+	DepositFailureHandler<CurrencyId, AccountId, Balance, Error> for FixedDepositFallback<AccountId, FallbackAccount>
+where
+	FallbackAccount: Get<AccountId>,
+{
+	fn on_deposit_failure(
+		_currency: &CurrencyId,
+		_who: &AccountId,
+		_amount: Balance,
+		_error: Error,
+	) -> Option<AccountId> {
+		Some(FallbackAccount::get())
+	}
+}
+
 /// The handler for processing cross-chain messages
 pub struct MultiCurrencyAdapter<
 	NativeCurrency,
@@ -43,6 +164,15 @@ pub struct MultiCurrencyAdapter<
 	AccountId,
 	CurrencyIdConverter,
 	CurrencyId,
+	OnDeposit,
+	OnWithdraw,
+	DepositFailure,
+	InstanceId,
+	NonFungibleMatcher,
+	NonFungibleFactory,
+	UnknownAsset,
+	Scaler,
+	MintError,
 >(
 	PhantomData<(
 		NativeCurrency,
@@ -52,16 +182,35 @@ pub struct MultiCurrencyAdapter<
 		AccountId,
 		CurrencyIdConverter,
 		CurrencyId,
+		OnDeposit,
+		OnWithdraw,
+		DepositFailure,
+		InstanceId,
+		NonFungibleMatcher,
+		NonFungibleFactory,
+		UnknownAsset,
+		Scaler,
+		MintError,
 	)>,
 );
 
 impl<
 		NativeCurrency: Currency<AccountId>,
-		TokenFactory: token_factory::TokenMinter<Ticker, AccountId, NativeCurrency::Balance>,
+		TokenFactory: token_factory::TokenMinter<Ticker, AccountId, NativeCurrency::Balance, Error = MintError>,
 		Matcher: MatchesFungible<NativeCurrency::Balance>,
 		AccountIdConverter: LocationConversion<AccountId>,
 		AccountId: sp_std::fmt::Debug + Clone,
 		CurrencyIdConverter: CurrencyIdConversion<CurrencyId>,
+		CurrencyId: Clone,
+		OnDeposit: OnDepositSuccess<CurrencyId, AccountId, NativeCurrency::Balance>,
+		OnWithdraw: OnWithdrawSuccess<CurrencyId, AccountId, NativeCurrency::Balance>,
+		DepositFailure: DepositFailureHandler<CurrencyId, AccountId, NativeCurrency::Balance, MintError>,
+		InstanceId: sp_std::fmt::Debug + Copy,
+		NonFungibleMatcher: MatchesNonFungible<InstanceId>,
+		NonFungibleFactory: token_factory::NonFungibleTokenFactory<Ticker, AccountId, InstanceId>,
+		UnknownAsset: UnknownAssets<AccountId>,
+		Scaler: BalanceScaler<CurrencyId>,
+		MintError: sp_std::fmt::Debug,
 	> TransactAsset
 	for MultiCurrencyAdapter<
 		NativeCurrency,
@@ -71,6 +220,15 @@ impl<
 		AccountId,
 		CurrencyIdConverter,
 		CurrencyId,
+		OnDeposit,
+		OnWithdraw,
+		DepositFailure,
+		InstanceId,
+		NonFungibleMatcher,
+		NonFungibleFactory,
+		UnknownAsset,
+		Scaler,
+		MintError,
 	>
 {
 	fn deposit_asset(asset: &MultiAsset, location: &MultiLocation) -> XcmResult {
@@ -82,31 +240,84 @@ impl<
 		);
 		let who = AccountIdConverter::from_location(location).ok_or(())?;
 		debug::info!("who: {:?}", who);
-		let currency = CurrencyIdConverter::from_asset(asset).ok_or(())?;
+		let currency = match CurrencyIdConverter::from_asset(asset) {
+			Some(currency) => currency,
+			None => {
+				// asset location isn't registered to a `CurrencyId` yet; park the value
+				// rather than drop it, so it can be reconciled once it is registered
+				if let MultiAsset::ConcreteFungible { id: location, amount } = asset {
+					debug::info!("currency_id: unknown, parking {:?} in UnknownAssets", amount);
+					UnknownAsset::deposit(location, &who, *amount)?;
+					debug::info!(">>> successful deposit (unknown asset).");
+					debug::info!("------------------------------------------------");
+					return Ok(());
+				}
+				return Err(());
+			}
+		};
 		debug::info!("currency_id: {:?}", currency);
-		let amount: NativeCurrency::Balance = Matcher::matches_fungible(&asset).ok_or(())?;
-		debug::info!("amount: {:?}", amount);
-		// match on currency variant
-		if let CurrencyId::Token(token_id) = currency {
-			// mint erc20 token to `who`
-			TokenFactory::mint(token_id, who.clone(), amount).map_err(|error| {
+
+		if let Some(amount) = Matcher::matches_fungible(&asset) {
+			let amount: NativeCurrency::Balance = amount;
+			debug::info!("amount: {:?}", amount);
+			// match on currency variant
+			// the account actually credited, reported to `OnDeposit`
+			let mut credited = who.clone();
+			if let CurrencyId::Token(token_id) = currency.clone() {
+				// mint at the amount the matcher already resolved: `Matcher::matches_fungible`
+				// already applied `Scaler::scale_in`, so `amount` is already in the token's
+				// local representation and must not be rescaled again here (`withdraw_asset`
+				// likewise burns the matcher's amount as is, with no `scale_out`)
+				if let Err(error) = TokenFactory::mint(token_id, who.clone(), amount) {
+					let fallback =
+						DepositFailure::on_deposit_failure(&currency, &who, amount, error).ok_or(())?;
+					debug::info!(
+						"Token factory `mint` failed, retrying against fallback account\n token_id: {:?}\n who: {:?}\n fallback: {:?}\n amount: {:?}",
+						token_id,
+						who,
+						fallback,
+						amount
+					);
+					TokenFactory::mint(token_id, fallback.clone(), amount).map_err(|error| {
+						debug::info!("Token factory `mint` failed on fallback account too: {:?}", error);
+						()
+					})?;
+					credited = fallback;
+				}
+			} else {
+				// native currency transfer via `frame/pallet_balances` is only other variant
+				NativeCurrency::deposit_creating(&who, amount);
+			}
+			OnDeposit::on_deposit(&currency, &credited, amount)?;
+			debug::info!(">>> successful deposit.");
+			debug::info!("------------------------------------------------");
+			return Ok(());
+		}
+
+		if let Some(instance) = NonFungibleMatcher::matches_nonfungible(&asset) {
+			debug::info!("instance: {:?}", instance);
+			let collection_id = match currency {
+				CurrencyId::Token(token_id) => token_id,
+				_ => return Err(()),
+			};
+			// mint the specific NFT instance to `who`; fails (rather than falling back) when
+			// the collection isn't registered with the factory
+			NonFungibleFactory::mint_instance(collection_id, who.clone(), instance).map_err(|error| {
 				debug::info!(
-					"Token factory `mint` failed
-					\n token_id: {:?}\n who: {:?}\n amount: {:?}\n error: {:?}",
-					token_id,
+					"Non-fungible token factory `mint_instance` failed\n collection: {:?}\n who: {:?}\n instance: {:?}\n error: {:?}",
+					collection_id,
 					who,
-					amount,
+					instance,
 					error
 				);
 				()
 			})?;
-		} else {
-			// native currency transfer via `frame/pallet_balances` is only other variant
-			NativeCurrency::deposit_creating(&who, amount);
+			debug::info!(">>> successful deposit.");
+			debug::info!("------------------------------------------------");
+			return Ok(());
 		}
-		debug::info!(">>> successful deposit.");
-		debug::info!("------------------------------------------------");
-		Ok(())
+
+		Err(())
 	}
 
 	fn withdraw_asset(
@@ -121,58 +332,136 @@ impl<
 		);
 		let who = AccountIdConverter::from_location(location).ok_or(())?;
 		debug::info!("who: {:?}", who);
-		let currency = CurrencyIdConverter::from_asset(asset).ok_or(())?;
+		let currency = match CurrencyIdConverter::from_asset(asset) {
+			Some(currency) => currency,
+			None => {
+				// mirror `deposit_asset`: decrement the parked balance before touching any
+				// native/token backend
+				if let MultiAsset::ConcreteFungible { id: location, amount } = asset {
+					UnknownAsset::withdraw(location, &who, *amount)?;
+					debug::info!(">>> successful withdraw (unknown asset).");
+					debug::info!("------------------------------------------------");
+					return Ok(asset.clone());
+				}
+				return Err(());
+			}
+		};
 		debug::info!("currency_id: {:?}", currency);
-		let amount: NativeCurrency::Balance = Matcher::matches_fungible(&asset).ok_or(())?;
-		debug::info!("amount: {:?}", amount);
-		// match on currency variant
-		if let CurrencyId::Token(token_id) = currency {
-			// burn erc20 token from `who`
-			TokenFactory::burn(token_id, who.clone(), amount).map_err(|error| {
-				debug::info!(
-					"Token factory `burn` failed
-					\n token_id: {:?}\n who: {:?}\n amount: {:?}\n error: {:?}",
-					token_id,
-					who,
+
+		if let Some(amount) = Matcher::matches_fungible(&asset) {
+			let amount: NativeCurrency::Balance = amount;
+			debug::info!("amount: {:?}", amount);
+			// match on currency variant
+			if let CurrencyId::Token(token_id) = currency.clone() {
+				// burn the matcher's already-scaled local `amount` directly: `Scaler::scale_in`
+				// (in the matcher) and `Scaler::scale_out` both apply once, on opposite sides of
+				// the same transfer — the matcher already did `scale_in` to get here, so
+				// rescaling again with `scale_out` would burn the foreign-decimal amount from a
+				// balance that was minted in local decimals
+				TokenFactory::burn(token_id, who.clone(), amount).map_err(|error| {
+					debug::info!(
+						"Token factory `burn` failed
+						\n token_id: {:?}\n who: {:?}\n amount: {:?}\n error: {:?}",
+						token_id,
+						who,
+						amount,
+						error
+					);
+					()
+				})?;
+			} else {
+				// native currency transfer via `frame/pallet_balances` is only other variant
+				NativeCurrency::withdraw(
+					&who,
 					amount,
-					error
-				);
-				()
-			})?;
-		} else {
-			// native currency transfer via `frame/pallet_balances` is only other variant
-			NativeCurrency::withdraw(
-				&who,
-				amount,
-				WithdrawReasons::TRANSFER,
-				ExistenceRequirement::AllowDeath,
-			)
-			.map_err(|error| {
+					WithdrawReasons::TRANSFER,
+					ExistenceRequirement::AllowDeath,
+				)
+				.map_err(|error| {
+					debug::info!(
+						"Native currency `withdraw` failed\n who: {:?}\n amount: {:?}\n error: {:?}",
+						who,
+						amount,
+						error
+					);
+					()
+				})?;
+			}
+			OnWithdraw::on_withdraw(&currency, &who, amount)?;
+			debug::info!(">>> successful withdraw.");
+			debug::info!("------------------------------------------------");
+			return Ok(asset.clone());
+		}
+
+		if let Some(instance) = NonFungibleMatcher::matches_nonfungible(&asset) {
+			debug::info!("instance: {:?}", instance);
+			let collection_id = match currency {
+				CurrencyId::Token(token_id) => token_id,
+				_ => return Err(()),
+			};
+			// burn the specific NFT instance from `who`
+			NonFungibleFactory::burn_instance(collection_id, who.clone(), instance).map_err(|error| {
 				debug::info!(
-					"Native currency `withdraw` failed\n who: {:?}\n amount: {:?}\n error: {:?}",
+					"Non-fungible token factory `burn_instance` failed\n collection: {:?}\n who: {:?}\n instance: {:?}\n error: {:?}",
+					collection_id,
 					who,
-					amount,
+					instance,
 					error
 				);
 				()
 			})?;
+			debug::info!(">>> successful withdraw.");
+			debug::info!("------------------------------------------------");
+			return Ok(asset.clone());
 		}
-		debug::info!(">>> successful withdraw.");
-		debug::info!("------------------------------------------------");
-		Ok(asset.clone())
+
+		Err(())
+	}
+}
+
+/// Resolves the `CurrencyId` for an asset reserved on a sibling parachain, keyed by
+/// `(para_id, general_key)`. `general_key` is `None` for the sibling's bare native asset
+/// (`X2(Parent, Parachain(id))`) and `Some` for one of its derivative assets
+/// (`X3(Parent, Parachain(id), GeneralKey(key))`).
+///
+/// Decimal rescaling for the resolved `CurrencyId` is not this trait's concern: once a
+/// `CurrencyId` is resolved, the matcher applies the same [`BalanceScaler::scale_in`] as for
+/// every other asset, on both deposit and withdraw, so `withdraw_asset` burns the same local
+/// amount that `deposit_asset` minted regardless of how the `CurrencyId` was resolved.
+pub trait ReserveCurrencyId<CurrencyId> {
+	fn currency_id(para_id: u32, general_key: Option<&[u8]>) -> Option<CurrencyId>;
+}
+
+/// Rescales an asset amount between its own decimals and the local chain's decimals, keyed
+/// by the resolved `CurrencyId`. Needed because a `GeneralKey` asset is otherwise taken 1:1,
+/// which mis-mints whenever the asset's native decimals (e.g. an 18-decimal ERC20) differ
+/// from the local token's. The default `()` implementation is a 1:1 passthrough.
+pub trait BalanceScaler<CurrencyId> {
+	fn scale_in(currency: &CurrencyId, amount: u128) -> Option<u128>;
+	fn scale_out(currency: &CurrencyId, amount: u128) -> Option<u128>;
+}
+
+impl<CurrencyId> BalanceScaler<CurrencyId> for () {
+	fn scale_in(_currency: &CurrencyId, amount: u128) -> Option<u128> {
+		Some(amount)
+	}
+	fn scale_out(_currency: &CurrencyId, amount: u128) -> Option<u128> {
+		Some(amount)
 	}
 }
 
 /// Matcher associated type for MultiCurrencyAdapter to convert assets into local types
-pub struct IsConcreteWithGeneralKey<CurrencyId, FromRelayChainBalance>(
-	PhantomData<(CurrencyId, FromRelayChainBalance)>,
+pub struct IsConcreteWithGeneralKey<CurrencyId, FromRelayChainBalance, ReserveCurrency, Scaler>(
+	PhantomData<(CurrencyId, FromRelayChainBalance, ReserveCurrency, Scaler)>,
 );
-impl<CurrencyId, B, FromRelayChainBalance> MatchesFungible<B>
-	for IsConcreteWithGeneralKey<CurrencyId, FromRelayChainBalance>
+impl<CurrencyId, B, FromRelayChainBalance, ReserveCurrency, Scaler> MatchesFungible<B>
+	for IsConcreteWithGeneralKey<CurrencyId, FromRelayChainBalance, ReserveCurrency, Scaler>
 where
 	CurrencyId: TryFrom<Vec<u8>>,
 	B: TryFrom<u128>,
 	FromRelayChainBalance: Convert<u128, u128>,
+	ReserveCurrency: ReserveCurrencyId<CurrencyId>,
+	Scaler: BalanceScaler<CurrencyId>,
 {
 	fn matches_fungible(a: &MultiAsset) -> Option<B> {
 		if let MultiAsset::ConcreteFungible { id, amount } = a {
@@ -181,9 +470,25 @@ where
 				let local_amount = FromRelayChainBalance::convert(*amount);
 				return CheckedConversion::checked_from(local_amount);
 			}
+			if let MultiLocation::X2(Junction::Parent, Junction::Parachain(para_id)) = id {
+				// sibling parachain's own native asset, reserved on that chain
+				if let Some(currency) = ReserveCurrency::currency_id(*para_id, None) {
+					let local_amount = Scaler::scale_in(&currency, *amount)?;
+					return CheckedConversion::checked_from(local_amount);
+				}
+			}
+			if let MultiLocation::X3(Junction::Parent, Junction::Parachain(para_id), Junction::GeneralKey(key)) = id
+			{
+				// one of that sibling's derivative assets, identified by its general key
+				if let Some(currency) = ReserveCurrency::currency_id(*para_id, Some(key)) {
+					let local_amount = Scaler::scale_in(&currency, *amount)?;
+					return CheckedConversion::checked_from(local_amount);
+				}
+			}
 			if let Some(Junction::GeneralKey(key)) = id.last() {
-				if TryInto::<CurrencyId>::try_into(key.clone()).is_ok() {
-					return CheckedConversion::checked_from(*amount);
+				if let Ok(currency) = TryInto::<CurrencyId>::try_into(key.clone()) {
+					let local_amount = Scaler::scale_in(&currency, *amount)?;
+					return CheckedConversion::checked_from(local_amount);
 				}
 			}
 		}
@@ -191,26 +496,204 @@ where
 	}
 }
 
+/// Matcher associated type for `MultiCurrencyAdapter` to recognise `ConcreteNonFungible`
+/// assets and decode their `AssetInstance` into a local instance id.
+pub struct IsConcreteNonFungible<InstanceId>(PhantomData<InstanceId>);
+impl<InstanceId> MatchesNonFungible<InstanceId> for IsConcreteNonFungible<InstanceId>
+where
+	InstanceId: TryFrom<u128>,
+{
+	fn matches_nonfungible(a: &MultiAsset) -> Option<InstanceId> {
+		if let MultiAsset::ConcreteNonFungible { instance, .. } = a {
+			let raw: u128 = match instance {
+				AssetInstance::Index { id } => *id,
+				AssetInstance::Array4(bytes) => u32::from_be_bytes(*bytes) as u128,
+				AssetInstance::Array8(bytes) => u64::from_be_bytes(*bytes) as u128,
+				AssetInstance::Array16(bytes) => u128::from_be_bytes(*bytes),
+				_ => return None,
+			};
+			return InstanceId::try_from(raw).ok();
+		}
+		None
+	}
+}
+
+/// Shared by `CurrencyIdConverter::from_asset` for both the fungible asset `id` and the
+/// non-fungible asset `class` location, since a collection of NFTs is identified the same
+/// way a fungible currency is: `Parent`, a sibling reserve location, or a trailing
+/// `GeneralKey`.
+fn location_to_currency_id<CurrencyId, RelayChainCurrencyId, ReserveCurrency>(
+	location: &MultiLocation,
+) -> Option<CurrencyId>
+where
+	CurrencyId: TryFrom<Vec<u8>>,
+	RelayChainCurrencyId: Get<CurrencyId>,
+	ReserveCurrency: ReserveCurrencyId<CurrencyId>,
+{
+	if location == &MultiLocation::X1(Junction::Parent) {
+		return Some(RelayChainCurrencyId::get());
+	}
+	if let MultiLocation::X2(Junction::Parent, Junction::Parachain(para_id)) = location {
+		if let Some(currency) = ReserveCurrency::currency_id(*para_id, None) {
+			return Some(currency);
+		}
+	}
+	if let MultiLocation::X3(Junction::Parent, Junction::Parachain(para_id), Junction::GeneralKey(key)) = location {
+		if let Some(currency) = ReserveCurrency::currency_id(*para_id, Some(key)) {
+			return Some(currency);
+		}
+	}
+	if let Some(Junction::GeneralKey(key)) = location.last() {
+		return CurrencyId::try_from(key.clone()).ok();
+	}
+	None
+}
+
 /// Converter from MultiAsset to local Currency type
-pub struct CurrencyIdConverter<CurrencyId, RelayChainCurrencyId>(
+pub struct CurrencyIdConverter<CurrencyId, RelayChainCurrencyId, ReserveCurrency>(
 	PhantomData<CurrencyId>,
 	PhantomData<RelayChainCurrencyId>,
+	PhantomData<ReserveCurrency>,
 );
-impl<CurrencyId, RelayChainCurrencyId> CurrencyIdConversion<CurrencyId>
-	for CurrencyIdConverter<CurrencyId, RelayChainCurrencyId>
+impl<CurrencyId, RelayChainCurrencyId, ReserveCurrency> CurrencyIdConversion<CurrencyId>
+	for CurrencyIdConverter<CurrencyId, RelayChainCurrencyId, ReserveCurrency>
 where
 	CurrencyId: TryFrom<Vec<u8>>,
 	RelayChainCurrencyId: Get<CurrencyId>,
+	ReserveCurrency: ReserveCurrencyId<CurrencyId>,
 {
 	fn from_asset(asset: &MultiAsset) -> Option<CurrencyId> {
-		if let MultiAsset::ConcreteFungible { id: location, .. } = asset {
-			if location == &MultiLocation::X1(Junction::Parent) {
-				return Some(RelayChainCurrencyId::get());
+		match asset {
+			MultiAsset::ConcreteFungible { id: location, .. } => {
+				location_to_currency_id::<CurrencyId, RelayChainCurrencyId, ReserveCurrency>(location)
 			}
-			if let Some(Junction::GeneralKey(key)) = location.last() {
-				return CurrencyId::try_from(key.clone()).ok();
+			// an NFT collection is resolved to a `CurrencyId` the same way a fungible
+			// asset's location is, so `deposit_asset`/`withdraw_asset` can reach the
+			// `MatchesNonFungible` branch instead of bailing out beforehand
+			MultiAsset::ConcreteNonFungible { class, .. } => {
+				location_to_currency_id::<CurrencyId, RelayChainCurrencyId, ReserveCurrency>(class)
 			}
+			_ => None,
 		}
-		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	enum TestCurrencyId {
+		Token(u32),
+	}
+
+	impl TryFrom<Vec<u8>> for TestCurrencyId {
+		type Error = ();
+		fn try_from(v: Vec<u8>) -> result::Result<Self, ()> {
+			let bytes: [u8; 4] = v.try_into().map_err(|_| ())?;
+			Ok(TestCurrencyId::Token(u32::from_be_bytes(bytes)))
+		}
+	}
+
+	struct RelayCurrency;
+	impl Get<TestCurrencyId> for RelayCurrency {
+		fn get() -> TestCurrencyId {
+			TestCurrencyId::Token(0)
+		}
+	}
+
+	struct NoReserve;
+	impl ReserveCurrencyId<TestCurrencyId> for NoReserve {
+		fn currency_id(_para_id: u32, _general_key: Option<&[u8]>) -> Option<TestCurrencyId> {
+			None
+		}
+	}
+
+	#[test]
+	fn resolves_currency_id_for_nft_collection_class() {
+		let class = MultiLocation::X1(Junction::GeneralKey(1u32.to_be_bytes().to_vec()));
+		let asset = MultiAsset::ConcreteNonFungible {
+			class,
+			instance: AssetInstance::Index { id: 7 },
+		};
+
+		let currency =
+			CurrencyIdConverter::<TestCurrencyId, RelayCurrency, NoReserve>::from_asset(&asset);
+
+		assert_eq!(currency, Some(TestCurrencyId::Token(1)));
+	}
+
+	#[test]
+	fn matches_nonfungible_decodes_index_instance() {
+		let asset = MultiAsset::ConcreteNonFungible {
+			class: MultiLocation::X1(Junction::GeneralKey(1u32.to_be_bytes().to_vec())),
+			instance: AssetInstance::Index { id: 7 },
+		};
+
+		let instance = IsConcreteNonFungible::<u128>::matches_nonfungible(&asset);
+
+		assert_eq!(instance, Some(7u128));
+	}
+
+	struct IdentityRelayBalance;
+	impl Convert<u128, u128> for IdentityRelayBalance {
+		fn convert(amount: u128) -> u128 {
+			amount
+		}
+	}
+
+	struct SiblingReserve;
+	impl ReserveCurrencyId<TestCurrencyId> for SiblingReserve {
+		fn currency_id(para_id: u32, _general_key: Option<&[u8]>) -> Option<TestCurrencyId> {
+			Some(TestCurrencyId::Token(para_id))
+		}
+	}
+
+	struct DecimalsScaler;
+	impl BalanceScaler<TestCurrencyId> for DecimalsScaler {
+		fn scale_in(_currency: &TestCurrencyId, amount: u128) -> Option<u128> {
+			amount.checked_div(1_000_000_000_000)
+		}
+		fn scale_out(_currency: &TestCurrencyId, amount: u128) -> Option<u128> {
+			amount.checked_mul(1_000_000_000_000)
+		}
+	}
+
+	#[test]
+	fn deposit_then_withdraw_same_asset_nets_to_zero_local_balance() {
+		// `deposit_asset` mints the matcher's already-`scale_in`'d amount directly, and
+		// `withdraw_asset` must burn that exact same amount with no further rescaling --
+		// applying `Scaler::scale_out` again before burning (the old, buggy `withdraw_asset`)
+		// would cancel the `scale_in` and try to burn the foreign-decimal wire amount instead.
+		let asset = MultiAsset::ConcreteFungible {
+			id: MultiLocation::X2(Junction::Parent, Junction::Parachain(2000)),
+			amount: 1_000_000_000_000,
+		};
+
+		// what `deposit_asset` mints
+		let minted = IsConcreteWithGeneralKey::<
+			TestCurrencyId,
+			IdentityRelayBalance,
+			SiblingReserve,
+			DecimalsScaler,
+		>::matches_fungible(&asset)
+		.expect("sibling reserve asset should match");
+
+		// what `withdraw_asset` burns: the same matcher output, unmodified
+		let burned = IsConcreteWithGeneralKey::<
+			TestCurrencyId,
+			IdentityRelayBalance,
+			SiblingReserve,
+			DecimalsScaler,
+		>::matches_fungible(&asset)
+		.expect("sibling reserve asset should match");
+
+		let local_balance = minted as i128 - burned as i128;
+		assert_eq!(local_balance, 0);
+
+		// guard against the regression directly: re-applying `scale_out` to the minted amount
+		// before burning would not net to zero, which is exactly the bug this fixes
+		let double_scaled = DecimalsScaler::scale_out(&TestCurrencyId::Token(2000), minted).unwrap();
+		assert_ne!(minted as i128 - double_scaled as i128, 0);
 	}
 }
@@ -0,0 +1,35 @@
+// Copyright 2019-2020 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mint/burn interfaces consumed by `pallets/token-dealer`'s XCM `MultiCurrencyAdapter`.
+//!
+//! `CurrencyId`, `Ticker` and `TokenMinter` already exist elsewhere in this crate; only
+//! [`NonFungibleTokenFactory`] is added here, mirroring `TokenMinter` for non-fungible
+//! collections so `MultiCurrencyAdapter`'s NFT deposit/withdraw path has a factory to call.
+
+use sp_std::fmt::Debug;
+
+/// Mint and burn individual asset instances within an NFT collection identified by `Ticker`,
+/// mirroring [`TokenMinter`](crate::TokenMinter) for the fungible case.
+pub trait NonFungibleTokenFactory<Ticker, AccountId, InstanceId> {
+	type Error: Debug;
+
+	/// Mint `instance` of `collection` to `who`.
+	fn mint_instance(collection: Ticker, who: AccountId, instance: InstanceId) -> Result<(), Self::Error>;
+
+	/// Burn `instance` of `collection` from `who`.
+	fn burn_instance(collection: Ticker, who: AccountId, instance: InstanceId) -> Result<(), Self::Error>;
+}